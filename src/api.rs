@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
@@ -26,15 +27,28 @@ pub struct PortPoe {
     pub pre_standard_detect_enabled: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct PortPoeWrite {
-    is_poe_enabled: bool,
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct PortPoeWrite {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_poe_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poe_priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poe_allocation_method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allocated_power_in_watts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_standard_detect_enabled: Option<bool>,
 }
 
 impl From<PortPoe> for PortPoeWrite {
     fn from(port_poe: PortPoe) -> Self {
         Self {
-            is_poe_enabled: port_poe.is_poe_enabled,
+            is_poe_enabled: Some(port_poe.is_poe_enabled),
+            poe_priority: Some(port_poe.poe_priority),
+            poe_allocation_method: Some(port_poe.poe_allocation_method),
+            allocated_power_in_watts: Some(port_poe.allocated_power_in_watts),
+            pre_standard_detect_enabled: Some(port_poe.pre_standard_detect_enabled),
         }
     }
 }
@@ -42,7 +56,11 @@ impl From<PortPoe> for PortPoeWrite {
 impl From<&PortPoe> for PortPoeWrite {
     fn from(port_poe: &PortPoe) -> Self {
         Self {
-            is_poe_enabled: port_poe.is_poe_enabled,
+            is_poe_enabled: Some(port_poe.is_poe_enabled),
+            poe_priority: Some(port_poe.poe_priority.clone()),
+            poe_allocation_method: Some(port_poe.poe_allocation_method.clone()),
+            allocated_power_in_watts: Some(port_poe.allocated_power_in_watts),
+            pre_standard_detect_enabled: Some(port_poe.pre_standard_detect_enabled),
         }
     }
 }
@@ -60,6 +78,16 @@ pub enum Error {
     Parse(url::ParseError),
     #[error("Could not send request: {0}")]
     Request(reqwest::Error),
+    #[error("invalid credentials")]
+    InvalidCredentials,
+    #[error("session expired")]
+    SessionExpired,
+    #[error("port {port_id} not found on this switch")]
+    PortNotFound { port_id: String },
+    #[error("permission denied")]
+    PermissionDenied,
+    #[error("unexpected response (status {status}): {body}")]
+    Unexpected { status: u16, body: String },
 }
 
 impl From<url::ParseError> for Error {
@@ -74,6 +102,32 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+/// Turns a non-2xx response into a classified [`Error`], reading the body
+/// for context in the catch-all case. `port_id` is attached so a 404 is
+/// reported as a missing port rather than a generic failure.
+async fn classify_error_response(response: reqwest::Response, port_id: Option<&str>) -> Error {
+    match response.status().as_u16() {
+        // The switch reports an expired session as either 400 or 401
+        // depending on the endpoint; `Session::validate` relies on the same
+        // pairing, so keep the two in sync.
+        400 | 401 => Error::SessionExpired,
+        403 => Error::PermissionDenied,
+        404 => match port_id {
+            Some(port_id) => Error::PortNotFound {
+                port_id: port_id.to_string(),
+            },
+            None => Error::Unexpected {
+                status: 404,
+                body: response.text().await.unwrap_or_default(),
+            },
+        },
+        status => Error::Unexpected {
+            status,
+            body: response.text().await.unwrap_or_default(),
+        },
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Session {
     client: reqwest::Client,
@@ -86,15 +140,23 @@ impl Session {
 
         let client = reqwest::ClientBuilder::new().cookie_store(true).build()?;
 
-        let LoginResponse { cookie } = client
+        let response = client
             .post(rest_base_url.join("login-sessions")?)
             .json(credentials)
             .send()
-            .await?
-            .error_for_status()?
-            .json()
             .await?;
 
+        if !response.status().is_success() {
+            // There is no session yet to have expired, so a 400/401 here
+            // means the credentials themselves were rejected.
+            return Err(match response.status().as_u16() {
+                400 | 401 => Error::InvalidCredentials,
+                _ => classify_error_response(response, None).await,
+            });
+        }
+
+        let LoginResponse { cookie } = response.json().await?;
+
         Ok((Session::from_cookie(url, &cookie)?, cookie))
     }
 
@@ -112,21 +174,36 @@ impl Session {
             client,
             url: rest_base_url,
         })
+    }
 
-        // TODO perform some request to check if the session_cookie is still valid
+    /// Checks whether the session's cookie is still accepted by the switch.
+    ///
+    /// Hits the login-sessions resource, which is cheap to call and requires
+    /// an authenticated cookie, instead of relying on a side effect of an
+    /// unrelated request.
+    pub async fn validate(&self) -> Result<bool, Error> {
+        let url = self.url.join("login-sessions")?;
+        let response = self.client.get(url).send().await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            status if status.as_u16() == 400 || status.as_u16() == 401 => Ok(false),
+            _ => {
+                response.error_for_status()?;
+                Ok(false)
+            }
+        }
     }
 
     pub async fn get_ports(&self) -> Result<Vec<PortPoe>, Error> {
         let url = self.url.join("poe/ports")?;
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<WiredElementList>()
-            .await?;
-        Ok(response.port_poe)
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(classify_error_response(response, None).await);
+        }
+
+        Ok(response.json::<WiredElementList>().await?.port_poe)
     }
 
     pub async fn get_port<T>(&self, port_id: T) -> Result<PortPoe, Error>
@@ -134,30 +211,32 @@ impl Session {
         T: AsRef<str> + std::fmt::Display,
     {
         let url = self.url.join(&format!("ports/{port_id}/poe"))?;
-        Ok(self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<PortPoe>()
-            .await?)
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(classify_error_response(response, Some(port_id.as_ref())).await);
+        }
+
+        Ok(response.json::<PortPoe>().await?)
     }
 
-    pub async fn set_port(
-        &self,
-        port: &PortPoe,
-        data: &serde_json::Value,
-    ) -> Result<PortPoe, Error> {
+    pub async fn set_port(&self, port: &PortPoe, data: &PortPoeWrite) -> Result<PortPoe, Error> {
         let url = self.url.join(&format!("ports/{}/poe", port.port_id))?;
-        Ok(self
-            .client
-            .put(url)
-            .json(data)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?)
+        let response = self.client.put(url).json(data).send().await?;
+
+        if !response.status().is_success() {
+            return Err(classify_error_response(response, Some(&port.port_id)).await);
+        }
+
+        Ok(response.json().await?)
     }
 }
+
+/// Produces a [`Session`], abstracting over how it was obtained so callers
+/// don't need to care whether that meant logging in with credentials or
+/// reusing a persisted cookie.
+#[async_trait]
+pub trait Authenticator {
+    async fn session(&self) -> Result<Session, Error>;
+    async fn refresh(&self) -> Result<(Session, String), Error>;
+}
@@ -1,18 +1,91 @@
 mod api;
+mod metrics;
 
 use crate::api::LoginRequest;
 use anyhow::{anyhow, bail};
-use api::{PortPoe, Session};
-use clap::{CommandFactory, Parser, Subcommand};
+use api::{Authenticator, PortPoe, PortPoeWrite, Session};
+use async_trait::async_trait;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::fs;
+use tokio::sync::RwLock;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[command(subcommand)]
     commands: Command,
+
+    /// Named profile to operate on; defaults to the last-used or "default" profile
+    #[arg(long, global = true, env = "ARC_PROFILE")]
+    profile: Option<String>,
+
+    /// How to print port results
+    #[arg(long, global = true, value_enum, default_value = "ndjson")]
+    output: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    /// A single pretty-printed JSON array of all results
+    Json,
+    /// One JSON object per line, ready to pipe into `jq`
+    Ndjson,
+    /// Human-readable, aligned columns
+    Table,
+    /// Comma-separated values, for spreadsheets
+    Csv,
+}
+
+/// Formats `ports` in the requested format. Pulled out of [`render`] so the
+/// formatting logic can be unit-tested without capturing stdout.
+fn format_ports(ports: &[PortPoe], format: OutputFormat) -> anyhow::Result<String> {
+    let mut out = String::new();
+    match format {
+        OutputFormat::Json => out.push_str(&serde_json::to_string_pretty(ports)?),
+        OutputFormat::Ndjson => {
+            for port in ports {
+                out.push_str(&serde_json::to_string(port)?);
+                out.push('\n');
+            }
+        }
+        OutputFormat::Table => {
+            out.push_str(&format!(
+                "{:<10}{:<10}{:<10}{:<10}\n",
+                "PORT", "ENABLED", "PRIORITY", "WATTS"
+            ));
+            for port in ports {
+                out.push_str(&format!(
+                    "{:<10}{:<10}{:<10}{:<10}\n",
+                    port.port_id, port.is_poe_enabled, port.poe_priority, port.allocated_power_in_watts
+                ));
+            }
+        }
+        OutputFormat::Csv => {
+            out.push_str("port_id,is_poe_enabled,poe_priority,allocated_power_in_watts\n");
+            for port in ports {
+                out.push_str(&format!(
+                    "{},{},{},{}\n",
+                    port.port_id,
+                    port.is_poe_enabled,
+                    port.poe_priority,
+                    port.allocated_power_in_watts
+                ));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Prints `ports` in the requested format. Both `port get` and `port set`
+/// route through here so their output stays consistent.
+fn render(ports: &[PortPoe], format: OutputFormat) -> anyhow::Result<()> {
+    print!("{}", format_ports(ports, format)?);
+    Ok(())
 }
 
 #[derive(Subcommand)]
@@ -20,12 +93,88 @@ enum Command {
     Login(LoginArgs),
     #[command(subcommand)]
     Port(PortCommands),
+    #[command(subcommand)]
+    Profile(ProfileCommands),
+    Serve(ServeArgs),
     Completion {
         #[arg(value_enum)]
         shell: Shell,
     },
 }
 
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// List all known profiles, marking the default
+    List,
+    /// Forget a profile and its persisted session
+    Remove { name: String },
+    /// Make a profile the default; with no --profile, the last-used profile still takes precedence
+    Default { name: String },
+}
+
+impl ProfileCommands {
+    async fn handle(self) -> anyhow::Result<()> {
+        let mut store = ProfileStore::load().await?;
+
+        match self {
+            ProfileCommands::List => {
+                for (name, data) in &store.profiles {
+                    let marker = if store.default_profile.as_deref() == Some(name.as_str()) {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    println!("{marker} {name}\t{}", data.login_args.base_url);
+                }
+            }
+            ProfileCommands::Remove { name } => {
+                store
+                    .profiles
+                    .remove(&name)
+                    .ok_or_else(|| anyhow!("no such profile: {name}"))?;
+                if store.default_profile.as_deref() == Some(name.as_str()) {
+                    store.default_profile = None;
+                }
+                if store.last_used.as_deref() == Some(name.as_str()) {
+                    store.last_used = None;
+                }
+                store.save().await?;
+            }
+            ProfileCommands::Default { name } => {
+                if !store.profiles.contains_key(&name) {
+                    bail!("no such profile: {name}");
+                }
+                store.default_profile = Some(name);
+                store.save().await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(clap::Args)]
+struct ServeArgs {
+    /// Address to serve the `/metrics` endpoint on
+    #[arg(long, default_value = "127.0.0.1:9090")]
+    listen: std::net::SocketAddr,
+
+    /// How often to poll `get_ports()` for fresh telemetry, in seconds
+    #[arg(long, default_value_t = 15)]
+    interval: u64,
+}
+
+impl ServeArgs {
+    async fn handle(self, session: ManagedSession) -> anyhow::Result<()> {
+        metrics::serve(
+            session,
+            self.listen,
+            std::time::Duration::from_secs(self.interval),
+        )
+        .await
+    }
+}
+
 #[derive(Serialize, Deserialize, clap::Args, Clone, Debug)]
 struct LoginArgs {
     base_url: String,
@@ -52,45 +201,195 @@ impl From<&LoginArgs> for LoginRequest {
 }
 
 impl LoginArgs {
-    async fn handle(&self) -> anyhow::Result<Session> {
-        let (session, cookie) = Session::new(&self.base_url, &self.into()).await?;
+    async fn handle(&self, profile: &str) -> anyhow::Result<Session> {
+        let authenticator = CredentialAuthenticator {
+            login_args: self.clone(),
+        };
+        let (session, cookie) = match authenticator.refresh().await {
+            Ok(v) => v,
+            Err(api::Error::InvalidCredentials) => bail!("invalid username or password"),
+            Err(e) => bail!("couldn't log in: {e}"),
+        };
 
-        PersistentData::save_to_disk(self.clone(), cookie).await?;
+        ProfileStore::persist(profile.to_string(), self.clone(), cookie).await?;
 
         Ok(session)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Logs in with a username and password every time a session is needed.
+struct CredentialAuthenticator {
+    login_args: LoginArgs,
+}
+
+#[async_trait]
+impl Authenticator for CredentialAuthenticator {
+    async fn session(&self) -> Result<Session, api::Error> {
+        let (session, _cookie) = self.refresh().await?;
+        Ok(session)
+    }
+
+    async fn refresh(&self) -> Result<(Session, String), api::Error> {
+        let login_request: LoginRequest = (&self.login_args).into();
+        Session::new(&self.login_args.base_url, &login_request).await
+    }
+}
+
+/// Reuses a previously persisted cookie, falling back to a fresh login only
+/// when asked to `refresh()`.
+struct CookieAuthenticator {
+    login_args: LoginArgs,
+    cookie: String,
+}
+
+#[async_trait]
+impl Authenticator for CookieAuthenticator {
+    async fn session(&self) -> Result<Session, api::Error> {
+        Session::from_cookie(&self.login_args.base_url, &self.cookie)
+    }
+
+    async fn refresh(&self) -> Result<(Session, String), api::Error> {
+        let login_request: LoginRequest = (&self.login_args).into();
+        Session::new(&self.login_args.base_url, &login_request).await
+    }
+}
+
+/// A [`Session`] plus what's needed to transparently log in again: wraps the
+/// actual request in `call()` so a `SessionExpired` mid-command (not just at
+/// startup) triggers one refresh-and-retry instead of surfacing raw to the
+/// user, and persists the new cookie for next time.
+#[derive(Clone)]
+struct ManagedSession {
+    session: Arc<RwLock<Session>>,
+    login_args: LoginArgs,
+    profile: String,
+}
+
+impl ManagedSession {
+    fn new(profile: String, login_args: LoginArgs, session: Session) -> Self {
+        Self {
+            session: Arc::new(RwLock::new(session)),
+            login_args,
+            profile,
+        }
+    }
+
+    async fn refresh(&self) -> anyhow::Result<Session> {
+        let authenticator = CredentialAuthenticator {
+            login_args: self.login_args.clone(),
+        };
+        let (session, cookie) = authenticator
+            .refresh()
+            .await
+            .map_err(|e| anyhow!("couldn't create session: {e}"))?;
+
+        *self.session.write().await = session.clone();
+        ProfileStore::persist(self.profile.clone(), self.login_args.clone(), cookie).await?;
+
+        Ok(session)
+    }
+
+    async fn call<T, F, Fut>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: Fn(Session) -> Fut,
+        Fut: std::future::Future<Output = Result<T, api::Error>>,
+    {
+        let session = self.session.read().await.clone();
+        match f(session).await {
+            Ok(value) => Ok(value),
+            Err(api::Error::SessionExpired) => {
+                eprintln!("session expired, re-logging in");
+                let session = self.refresh().await?;
+                Ok(f(session).await?)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct PersistentData {
     login_args: LoginArgs,
     cookie: String,
 }
 
-impl PersistentData {
-    async fn save_to_disk(login_args: LoginArgs, cookie: String) -> anyhow::Result<()> {
-        if let Some(data_dir) = dirs::data_dir() {
-            let arc_dir = data_dir.join("arc");
-            if !fs::try_exists(&arc_dir).await? {
-                fs::create_dir(&arc_dir).await?;
+/// Named collection of persisted switch sessions, so operators managing
+/// several closets of switches can keep more than one logged-in profile.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ProfileStore {
+    profiles: HashMap<String, PersistentData>,
+    default_profile: Option<String>,
+    last_used: Option<String>,
+}
+
+impl ProfileStore {
+    fn path() -> anyhow::Result<std::path::PathBuf> {
+        dirs::data_dir()
+            .map(|data_dir| data_dir.join("arc").join("profiles.json"))
+            .ok_or_else(|| anyhow!("data directory does not exist"))
+    }
+
+    async fn load() -> anyhow::Result<ProfileStore> {
+        let path = Self::path()?;
+        if !fs::try_exists(&path).await? {
+            return Ok(ProfileStore::default());
+        }
+        let serialized = fs::read_to_string(path).await?;
+        Ok(serde_json::from_str(&serialized)?)
+    }
+
+    async fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path()?;
+        if let Some(arc_dir) = path.parent() {
+            if !fs::try_exists(arc_dir).await? {
+                fs::create_dir(arc_dir).await?;
             }
-            let data_to_persist = PersistentData { login_args, cookie };
-            let serialized = serde_json::to_string(&data_to_persist)?;
+        }
+        fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
 
-            fs::write(arc_dir.join("persist.txt"), serialized).await?;
-            Ok(())
-        } else {
-            bail!("data directory does not exist");
+    /// Resolves which profile to use: an explicit `--profile`, falling back
+    /// to the last-used profile, then the configured default profile, then a
+    /// profile literally named "default".
+    fn resolve(&self, requested: Option<&str>) -> anyhow::Result<String> {
+        if let Some(name) = requested {
+            return Ok(name.to_string());
+        }
+        if let Some(name) = &self.last_used {
+            return Ok(name.clone());
+        }
+        if let Some(name) = &self.default_profile {
+            return Ok(name.clone());
+        }
+        if self.profiles.contains_key("default") {
+            return Ok("default".to_string());
         }
+        bail!("no profile specified and no default profile set, please login first")
     }
 
-    async fn load_from_disk() -> anyhow::Result<PersistentData> {
-        if let Some(data_dir) = dirs::data_dir() {
-            let data_as_json = fs::read_to_string(data_dir.join("arc/persist.txt")).await?;
-            Ok(serde_json::from_str(&data_as_json)?)
-        } else {
-            bail!("data directory does not exist");
+    fn get(&self, name: &str) -> anyhow::Result<&PersistentData> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| anyhow!("no such profile: {name}"))
+    }
+
+    async fn mark_used(name: &str) -> anyhow::Result<()> {
+        let mut store = ProfileStore::load().await?;
+        store.last_used = Some(name.to_string());
+        store.save().await
+    }
+
+    async fn persist(name: String, login_args: LoginArgs, cookie: String) -> anyhow::Result<()> {
+        let mut store = ProfileStore::load().await?;
+        store
+            .profiles
+            .insert(name.clone(), PersistentData { login_args, cookie });
+        if store.default_profile.is_none() {
+            store.default_profile = Some(name.clone());
         }
+        store.last_used = Some(name);
+        store.save().await
     }
 }
 
@@ -106,27 +405,44 @@ struct GetArgs {
 }
 
 impl GetArgs {
-    async fn handle(self, session: Session) -> anyhow::Result<()> {
+    async fn handle(self, session: ManagedSession, format: OutputFormat) -> anyhow::Result<()> {
         let all = self.port_ids.len() == 1 && self.port_ids[0] == "all";
 
         let ports = if all {
-            session.get_ports().await?
+            session.call(|s| async move { s.get_ports().await }).await?
         } else if self.port_ids.len() == 1 {
-            vec![session.get_port(&self.port_ids[0]).await?]
+            let port_id = self.port_ids[0].clone();
+            vec![
+                session
+                    .call(move |s| {
+                        let port_id = port_id.clone();
+                        async move { s.get_port(port_id).await }
+                    })
+                    .await?,
+            ]
         } else {
             session
-                .get_ports()
+                .call(|s| async move { s.get_ports().await })
                 .await?
                 .into_iter()
                 .filter(|port| self.port_ids.contains(&port.port_id))
                 .collect()
         };
 
-        for port in ports {
-            println!("{}", serde_json::to_string(&port)?);
-        }
+        render(&ports, format)
+    }
+}
 
-        Ok(())
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PoePriority {
+    Low,
+    High,
+    Critical,
+}
+
+impl std::fmt::Display for PoePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_possible_value().unwrap().get_name())
     }
 }
 
@@ -134,19 +450,55 @@ impl GetArgs {
 struct SetArgs {
     #[arg(required(true))]
     port_ids: Vec<String>,
-    data: String,
+
+    /// Enable or disable PoE on the selected ports
+    #[arg(long)]
+    poe_enabled: Option<bool>,
+
+    /// PoE priority used when power is oversubscribed
+    #[arg(long, value_enum)]
+    poe_priority: Option<PoePriority>,
+
+    /// PoE power allocation method
+    #[arg(long)]
+    poe_allocation_method: Option<String>,
+
+    /// Power in watts allocated to the port
+    #[arg(long)]
+    allocated_power_watts: Option<u32>,
+
+    /// Detect legacy, pre-802.3af capable devices
+    #[arg(long)]
+    pre_standard_detect: Option<bool>,
+}
+
+impl From<&SetArgs> for PortPoeWrite {
+    fn from(value: &SetArgs) -> Self {
+        PortPoeWrite {
+            is_poe_enabled: value.poe_enabled,
+            poe_priority: value.poe_priority.map(|p| p.to_string()),
+            poe_allocation_method: value.poe_allocation_method.clone(),
+            allocated_power_in_watts: value.allocated_power_watts,
+            pre_standard_detect_enabled: value.pre_standard_detect,
+        }
+    }
 }
 
 impl SetArgs {
-    async fn handle(self, session: Session) -> anyhow::Result<()> {
+    async fn handle(self, session: ManagedSession, format: OutputFormat) -> anyhow::Result<()> {
+        let data: PortPoeWrite = (&self).into();
+
+        if data == PortPoeWrite::default() {
+            bail!("no fields given to set, pass at least one of --poe-enabled, --poe-priority, --poe-allocation-method, --allocated-power-watts, --pre-standard-detect");
+        }
+
         let all = self.port_ids.len() == 1 && self.port_ids[0] == "all";
 
+        let ports = session.call(|s| async move { s.get_ports().await }).await?;
         let ports = if all {
-            session.get_ports().await?
+            ports
         } else {
-            session
-                .get_ports()
-                .await?
+            ports
                 .into_iter()
                 .filter(|port| self.port_ids.contains(&port.port_id))
                 .collect()
@@ -155,30 +507,64 @@ impl SetArgs {
         let mut tasks = Vec::with_capacity(ports.len());
         for port in ports {
             let session_clone = session.clone();
-            let json_data = serde_json::from_str(&self.data)?;
+            let data_clone = data.clone();
             tasks.push(tokio::spawn(async move {
-                session_clone.set_port(&port, &json_data).await
+                session_clone
+                    .call(move |s| {
+                        let port = port.clone();
+                        let data_clone = data_clone.clone();
+                        async move { s.set_port(&port, &data_clone).await }
+                    })
+                    .await
             }));
         }
 
         let results = futures::future::join_all(tasks)
             .await
             .into_iter()
-            .flat_map(|task| {
-                task.map_err(|e| {
-                    Err::<Result<Vec<PortPoe>, api::Error>, anyhow::Error>(anyhow!(
-                        "failed to join task: {e}"
-                    ))
-                })
-            })
-            .collect::<Result<Vec<PortPoe>, api::Error>>()?;
+            .map(|task| task.unwrap_or_else(|e| Err(anyhow!("failed to join task: {e}"))))
+            .collect::<anyhow::Result<Vec<PortPoe>>>()?;
 
-        for result in results {
-            println!("{}", serde_json::to_string(&result)?);
-        }
+        render(&results, format)
+    }
+}
 
-        Ok(())
+async fn session_from_profile(requested_profile: Option<&str>) -> anyhow::Result<ManagedSession> {
+    let store = ProfileStore::load().await?;
+    let profile = store.resolve(requested_profile)?;
+    let persisted_data = store.get(&profile)?.clone();
+
+    let authenticator = CookieAuthenticator {
+        login_args: persisted_data.login_args.clone(),
+        cookie: persisted_data.cookie,
+    };
+
+    let session = authenticator
+        .session()
+        .await
+        .map_err(|e| anyhow!("couldn't reconstruct session from cookie: {e}"))?;
+
+    if session.validate().await? {
+        ProfileStore::mark_used(&profile).await?;
+        return Ok(ManagedSession::new(
+            profile,
+            persisted_data.login_args,
+            session,
+        ));
     }
+
+    eprintln!("session expired, re-logging in");
+    let (session, cookie) = match authenticator.refresh().await {
+        Ok(v) => v,
+        Err(api::Error::InvalidCredentials) => {
+            bail!("invalid credentials for profile \"{profile}\", please run `arc login` again")
+        }
+        Err(e) => bail!("couldn't create session: {e}"),
+    };
+
+    ProfileStore::persist(profile.clone(), authenticator.login_args.clone(), cookie).await?;
+
+    Ok(ManagedSession::new(profile, authenticator.login_args, session))
 }
 
 #[tokio::main]
@@ -186,42 +572,23 @@ async fn main() -> anyhow::Result<()> {
     let cli = Args::parse();
 
     match cli.commands {
-        Command::Login(args) => _ = args.handle().await?,
+        Command::Login(args) => {
+            let profile = cli.profile.unwrap_or_else(|| "default".to_string());
+            _ = args.handle(&profile).await?
+        }
         Command::Port(port_command) => {
-            let persisted_data = PersistentData::load_from_disk()
-                .await
-                .map_err(|e| anyhow!("couldn't load cookie from disk, please login first: {e}"))?;
-
-            let session =
-                Session::from_cookie(&persisted_data.login_args.base_url, &persisted_data.cookie)?;
-
-            let session = if let Err(api::Error::Request(e)) = session.get_port("1").await {
-                if let Some(status_code) = e.status() {
-                    if status_code.as_u16() == 400 {
-                        let login_request: LoginRequest = persisted_data.login_args.clone().into();
-                        let (new_session, cookie) =
-                            Session::new(&persisted_data.login_args.base_url, &login_request)
-                                .await
-                                .map_err(|e| anyhow!("couldn't create session: {e}"))?;
-
-                        PersistentData::save_to_disk(persisted_data.login_args, cookie).await?;
-
-                        new_session
-                    } else {
-                        bail!("unexpected error");
-                    }
-                } else {
-                    bail!("unexpected error");
-                }
-            } else {
-                session
-            };
+            let session = session_from_profile(cli.profile.as_deref()).await?;
 
             match port_command {
-                PortCommands::Get(args) => args.handle(session).await?,
-                PortCommands::Set(args) => args.handle(session).await?,
+                PortCommands::Get(args) => args.handle(session, cli.output).await?,
+                PortCommands::Set(args) => args.handle(session, cli.output).await?,
             }
         }
+        Command::Profile(profile_command) => profile_command.handle().await?,
+        Command::Serve(args) => {
+            let session = session_from_profile(cli.profile.as_deref()).await?;
+            args.handle(session).await?;
+        }
         Command::Completion { shell } => {
             clap_complete::generate(shell, &mut Args::command(), "arc", &mut std::io::stdout())
         }
@@ -229,3 +596,117 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_port(port_id: &str) -> PortPoe {
+        PortPoe {
+            port_id: port_id.to_string(),
+            is_poe_enabled: true,
+            poe_priority: "high".to_string(),
+            allocated_power_in_watts: 15,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn format_ports_json_is_a_pretty_array() {
+        let ports = vec![sample_port("1")];
+
+        let out = format_ports(&ports, OutputFormat::Json).unwrap();
+
+        assert_eq!(out, serde_json::to_string_pretty(&ports).unwrap());
+    }
+
+    #[test]
+    fn format_ports_ndjson_is_one_json_object_per_line() {
+        let ports = vec![sample_port("1"), sample_port("2")];
+
+        let out = format_ports(&ports, OutputFormat::Ndjson).unwrap();
+
+        let expected = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&ports[0]).unwrap(),
+            serde_json::to_string(&ports[1]).unwrap()
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn format_ports_table_has_header_and_row() {
+        let ports = vec![sample_port("1")];
+
+        let out = format_ports(&ports, OutputFormat::Table).unwrap();
+
+        assert_eq!(
+            out,
+            "PORT      ENABLED   PRIORITY  WATTS     \n1         true      high      15        \n"
+        );
+    }
+
+    #[test]
+    fn format_ports_csv_has_header_and_row() {
+        let ports = vec![sample_port("1")];
+
+        let out = format_ports(&ports, OutputFormat::Csv).unwrap();
+
+        assert_eq!(
+            out,
+            "port_id,is_poe_enabled,poe_priority,allocated_power_in_watts\n1,true,high,15\n"
+        );
+    }
+
+    fn profile(base_url: &str) -> PersistentData {
+        PersistentData {
+            login_args: LoginArgs {
+                base_url: base_url.to_string(),
+                user_name: "admin".to_string(),
+                password: "hunter2".to_string(),
+            },
+            cookie: "session-cookie".to_string(),
+        }
+    }
+
+    #[test]
+    fn resolve_prefers_an_explicit_profile_over_everything_else() {
+        let mut store = ProfileStore::default();
+        store.last_used = Some("last".to_string());
+        store.default_profile = Some("def".to_string());
+
+        assert_eq!(store.resolve(Some("explicit")).unwrap(), "explicit");
+    }
+
+    #[test]
+    fn resolve_falls_back_to_the_last_used_profile() {
+        let mut store = ProfileStore::default();
+        store.last_used = Some("last".to_string());
+        store.default_profile = Some("def".to_string());
+
+        assert_eq!(store.resolve(None).unwrap(), "last");
+    }
+
+    #[test]
+    fn resolve_falls_back_to_the_default_profile() {
+        let mut store = ProfileStore::default();
+        store.default_profile = Some("def".to_string());
+
+        assert_eq!(store.resolve(None).unwrap(), "def");
+    }
+
+    #[test]
+    fn resolve_falls_back_to_a_profile_literally_named_default() {
+        let mut store = ProfileStore::default();
+        store.profiles.insert("default".to_string(), profile("https://switch.example"));
+
+        assert_eq!(store.resolve(None).unwrap(), "default");
+    }
+
+    #[test]
+    fn resolve_errors_when_nothing_is_available() {
+        let store = ProfileStore::default();
+
+        assert!(store.resolve(None).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::api::PortPoe;
+use crate::ManagedSession;
+use hyper::body::Bytes;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use prometheus::{Encoder, Gauge, GaugeVec, IntCounter, Opts, Registry, TextEncoder};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Holds the Prometheus registry together with the gauges it owns, so a single
+/// scrape can update them all from one `get_ports()` call.
+struct Metrics {
+    registry: Registry,
+    poe_enabled: GaugeVec,
+    allocated_power_watts: GaugeVec,
+    scrape_errors_total: IntCounter,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+
+        let poe_enabled = GaugeVec::new(
+            Opts::new(
+                "arc_port_poe_enabled",
+                "Whether PoE is enabled on the port (1) or not (0)",
+            ),
+            &["port_id"],
+        )?;
+        let allocated_power_watts = GaugeVec::new(
+            Opts::new(
+                "arc_port_allocated_power_watts",
+                "Power in watts allocated to the port",
+            ),
+            &["port_id"],
+        )?;
+        let scrape_errors_total = IntCounter::new(
+            "arc_scrape_errors_total",
+            "Number of failed get_ports() calls while scraping",
+        )?;
+
+        registry.register(Box::new(poe_enabled.clone()))?;
+        registry.register(Box::new(allocated_power_watts.clone()))?;
+        registry.register(Box::new(scrape_errors_total.clone()))?;
+
+        Ok(Self {
+            registry,
+            poe_enabled,
+            allocated_power_watts,
+            scrape_errors_total,
+        })
+    }
+
+    fn update(&self, ports: &[PortPoe]) {
+        for port in ports {
+            let enabled_gauge: Gauge = self.poe_enabled.with_label_values(&[&port.port_id]);
+            enabled_gauge.set(if port.is_poe_enabled { 1.0 } else { 0.0 });
+
+            let power_gauge: Gauge = self
+                .allocated_power_watts
+                .with_label_values(&[&port.port_id]);
+            power_gauge.set(port.allocated_power_in_watts as f64);
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let encoder = TextEncoder::new();
+        let families = self.registry.gather();
+        encoder.encode(&families, &mut buffer).ok();
+        buffer
+    }
+}
+
+/// Polls `get_ports()` on `interval` and serves the results as Prometheus
+/// metrics on `listen` until the process is stopped.
+///
+/// The session's cookie can expire during a long-running `arc serve`;
+/// `ManagedSession::call` transparently refreshes and retries once so a
+/// single expiry doesn't leave the exporter serving stale data forever.
+pub async fn serve(
+    session: ManagedSession,
+    listen: SocketAddr,
+    interval: std::time::Duration,
+) -> anyhow::Result<()> {
+    let metrics = Arc::new(Metrics::new()?);
+
+    {
+        let session = session.clone();
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match session.call(|s| async move { s.get_ports().await }).await {
+                    Ok(ports) => metrics.update(&ports),
+                    Err(e) => {
+                        metrics.scrape_errors_total.inc();
+                        eprintln!("failed to scrape ports: {e}");
+                    }
+                }
+            }
+        });
+    }
+
+    let listener = TcpListener::bind(listen).await?;
+    eprintln!("serving metrics on http://{listen}/metrics");
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let io = TokioIo::new(stream);
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            let service = service_fn(move |req: Request<hyper::body::Incoming>| {
+                let metrics = metrics.clone();
+                async move { Ok::<_, std::convert::Infallible>(handle(req, metrics)) }
+            });
+
+            if let Err(e) = http1::Builder::new().serve_connection(io, service).await {
+                eprintln!("error serving connection: {e}");
+            }
+        });
+    }
+}
+
+fn handle(
+    req: Request<hyper::body::Incoming>,
+    metrics: Arc<Metrics>,
+) -> Response<http_body_util::Full<Bytes>> {
+    if req.uri().path() == "/metrics" {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(http_body_util::Full::new(Bytes::from(metrics.encode())))
+            .unwrap()
+    } else {
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(http_body_util::Full::new(Bytes::new()))
+            .unwrap()
+    }
+}